@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use crate::errors::HardwareError;
+use crate::types::{PrevTxMeta, SignTx, TxRequest, TxRequestType};
+
+/// Drives the device's raw `TxRequest`/`TxAck` `signTx` dialogue to
+/// completion, for a transport that streams those steps back one at a time
+/// instead of completing the whole signing flow in one bridge round trip
+/// (see [`crate::TrezorClient::sign_tx`]).
+///
+/// The device answers the initial `signTx` command with a stream of
+/// `TxRequest` messages; each must be answered with exactly the piece of
+/// data it asked for via [`Self::build_ack`], after which the device's next
+/// `TxRequest` is fed back in through [`Self::advance`]. Once
+/// [`Self::is_finished`] reports `TXFINISHED`, [`Self::finish`] returns the
+/// assembled raw signed transaction.
+pub struct SignTxProgress {
+    sign_tx: SignTx,
+    prev_txs: HashMap<String, PrevTxMeta>,
+    pending: TxRequest,
+    serialized_tx: String,
+}
+
+impl SignTxProgress {
+    pub(crate) fn new(sign_tx: SignTx, prev_txs: HashMap<String, PrevTxMeta>, first_request: TxRequest) -> Self {
+        let mut progress = Self { sign_tx, prev_txs, pending: first_request, serialized_tx: String::new() };
+        progress.absorb_serialized();
+        progress
+    }
+
+    /// True once the device has reported `TXFINISHED`.
+    pub fn is_finished(&self) -> bool {
+        self.pending.request_type == TxRequestType::TxFinished
+    }
+
+    /// The request the device is currently waiting to be acked.
+    pub fn pending_request(&self) -> &TxRequest {
+        &self.pending
+    }
+
+    /// Build the `TxAck` payload that answers the current pending request.
+    pub fn build_ack(&self) -> Result<Value, HardwareError> {
+        match self.pending.request_type {
+            TxRequestType::TxInput => self.ack_input(),
+            TxRequestType::TxOutput => self.ack_output(),
+            TxRequestType::TxMeta => self.ack_prev_meta(),
+            TxRequestType::TxFinished => Err(HardwareError::CommunicationError {
+                error_details: "no ack is needed once the device reports TXFINISHED".into(),
+            }),
+        }
+    }
+
+    /// Feed in the `TxRequest` the device replied with after the last ack.
+    ///
+    /// There is no monotonicity guarantee to enforce here: the device
+    /// legitimately re-requests the same input (or the same index under a
+    /// different `request_type`) more than once across the signing dialogue,
+    /// e.g. once while it's verifying the inputs and again while it's
+    /// streaming signatures.
+    pub fn advance(&mut self, next: TxRequest) -> Result<(), HardwareError> {
+        self.pending = next;
+        self.absorb_serialized();
+        Ok(())
+    }
+
+    /// Consume the progress and return the assembled raw signed
+    /// transaction, once the device has reported `TXFINISHED`.
+    pub fn finish(self) -> Result<String, HardwareError> {
+        if !self.is_finished() {
+            return Err(HardwareError::CommunicationError {
+                error_details: "sign_tx was abandoned before the device reported TXFINISHED".into(),
+            });
+        }
+        Ok(self.serialized_tx)
+    }
+
+    fn absorb_serialized(&mut self) {
+        if let Some(chunk) = &self.pending.serialized {
+            if let Some(tx) = &chunk.serialized_tx {
+                self.serialized_tx.push_str(tx);
+            }
+        }
+    }
+
+    fn ack_input(&self) -> Result<Value, HardwareError> {
+        let index = self.pending.details.request_index as usize;
+        let input = match &self.pending.details.tx_hash {
+            Some(hash) => self.prev_tx(hash)?.inputs.get(index),
+            None => self.sign_tx.inputs.get(index),
+        }
+        .ok_or_else(|| HardwareError::CommunicationError {
+            error_details: format!("device requested input {index}, which was never supplied"),
+        })?;
+        Ok(json!({ "input": input }))
+    }
+
+    fn ack_output(&self) -> Result<Value, HardwareError> {
+        let index = self.pending.details.request_index as usize;
+        let output = match &self.pending.details.tx_hash {
+            Some(hash) => self.prev_tx(hash)?.outputs.get(index),
+            None => self.sign_tx.outputs.get(index),
+        }
+        .ok_or_else(|| HardwareError::CommunicationError {
+            error_details: format!("device requested output {index}, which was never supplied"),
+        })?;
+        Ok(json!({ "output": output }))
+    }
+
+    fn ack_prev_meta(&self) -> Result<Value, HardwareError> {
+        let hash = self.pending.details.tx_hash.as_deref().ok_or_else(|| HardwareError::CommunicationError {
+            error_details: "TXMETA request carried no tx_hash to look up".into(),
+        })?;
+        let meta = self.prev_tx(hash)?;
+        Ok(json!({
+            "version": meta.version,
+            "lockTime": meta.lock_time,
+            "inputsCnt": meta.inputs.len(),
+            "outputsCnt": meta.outputs.len(),
+        }))
+    }
+
+    fn prev_tx(&self, hash: &str) -> Result<&PrevTxMeta, HardwareError> {
+        self.prev_txs.get(hash).ok_or_else(|| HardwareError::CommunicationError {
+            error_details: format!("no previous transaction supplied for tx_hash {hash}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{SerializedChunk, TxInput, TxOutput, TxRequestDetails};
+
+    fn tx_input(amount: u64) -> TxInput {
+        TxInput {
+            address_n: vec![0x8000_002c, 0x8000_0000, 0x8000_0000],
+            prev_hash: "deadbeef".into(),
+            prev_index: 0,
+            amount,
+            script_type: None,
+            sequence: None,
+        }
+    }
+
+    fn tx_output(amount: u64) -> TxOutput {
+        TxOutput {
+            address: Some("1BoatSLRHtKNngkdXEeobR76b53LETtpyT".into()),
+            address_n: None,
+            amount,
+            script_type: "PAYTOADDRESS".into(),
+        }
+    }
+
+    fn request(request_type: TxRequestType, request_index: u32, tx_hash: Option<&str>) -> TxRequest {
+        TxRequest {
+            request_type,
+            details: TxRequestDetails { request_index, tx_hash: tx_hash.map(String::from) },
+            serialized: None,
+        }
+    }
+
+    #[test]
+    fn ack_input_uses_sign_tx_inputs_when_no_tx_hash_is_requested() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![tx_input(1_000)], outputs: vec![] };
+        let progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxInput, 0, None));
+
+        let ack = progress.build_ack().unwrap();
+        assert_eq!(ack["input"]["amount"], 1_000);
+    }
+
+    #[test]
+    fn ack_input_looks_up_the_previous_transaction_by_hash() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let mut prev_txs = HashMap::new();
+        prev_txs.insert(
+            "deadbeef".to_string(),
+            PrevTxMeta { version: 1, lock_time: 0, inputs: vec![tx_input(2_000)], outputs: vec![] },
+        );
+        let progress = SignTxProgress::new(sign_tx, prev_txs, request(TxRequestType::TxInput, 0, Some("deadbeef")));
+
+        let ack = progress.build_ack().unwrap();
+        assert_eq!(ack["input"]["amount"], 2_000);
+    }
+
+    #[test]
+    fn ack_input_errors_when_the_previous_transaction_is_missing() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxInput, 0, Some("missing")));
+
+        let err = progress.build_ack().unwrap_err();
+        assert!(matches!(err, HardwareError::CommunicationError { .. }));
+    }
+
+    #[test]
+    fn ack_input_errors_when_the_requested_index_was_never_supplied() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![tx_input(1_000)], outputs: vec![] };
+        let progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxInput, 5, None));
+
+        let err = progress.build_ack().unwrap_err();
+        assert!(matches!(err, HardwareError::CommunicationError { .. }));
+    }
+
+    #[test]
+    fn ack_output_uses_sign_tx_outputs_when_no_tx_hash_is_requested() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![tx_output(500)] };
+        let progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxOutput, 0, None));
+
+        let ack = progress.build_ack().unwrap();
+        assert_eq!(ack["output"]["amount"], 500);
+    }
+
+    #[test]
+    fn ack_prev_meta_reports_counts_and_version() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let mut prev_txs = HashMap::new();
+        prev_txs.insert(
+            "deadbeef".to_string(),
+            PrevTxMeta {
+                version: 2,
+                lock_time: 7,
+                inputs: vec![tx_input(1), tx_input(2)],
+                outputs: vec![tx_output(3)],
+            },
+        );
+        let progress = SignTxProgress::new(sign_tx, prev_txs, request(TxRequestType::TxMeta, 0, Some("deadbeef")));
+
+        let ack = progress.build_ack().unwrap();
+        assert_eq!(ack["version"], 2);
+        assert_eq!(ack["lockTime"], 7);
+        assert_eq!(ack["inputsCnt"], 2);
+        assert_eq!(ack["outputsCnt"], 1);
+    }
+
+    #[test]
+    fn serialized_tx_accumulates_across_advances_and_finish_requires_tx_finished() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let mut progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxInput, 0, None));
+
+        let mut first_chunk = request(TxRequestType::TxOutput, 0, None);
+        first_chunk.serialized =
+            Some(SerializedChunk { signature_index: None, signature: None, serialized_tx: Some("aa".into()) });
+        progress.advance(first_chunk).unwrap();
+        assert!(!progress.is_finished());
+
+        let mut second_chunk = request(TxRequestType::TxFinished, 0, None);
+        second_chunk.serialized =
+            Some(SerializedChunk { signature_index: None, signature: None, serialized_tx: Some("bb".into()) });
+        progress.advance(second_chunk).unwrap();
+
+        assert!(progress.is_finished());
+        assert_eq!(progress.finish().unwrap(), "aabb");
+    }
+
+    #[test]
+    fn finish_errors_when_the_device_never_reported_tx_finished() {
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let progress = SignTxProgress::new(sign_tx, HashMap::new(), request(TxRequestType::TxInput, 0, None));
+
+        let err = progress.finish().unwrap_err();
+        assert!(matches!(err, HardwareError::CommunicationError { .. }));
+    }
+}
@@ -0,0 +1,127 @@
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum HardwareError {
+    #[error("I/O error: {0}")]
+    IoError(#[source] std::io::Error),
+
+    #[error("Communication error: {error_details}")]
+    CommunicationError { error_details: String },
+
+    #[error("JSON error: {0}")]
+    JsonError(#[source] serde_json::Error),
+
+    #[error("Initialization error: {error_details}")]
+    InitializationError { error_details: String },
+
+    #[error("The user cancelled the operation on the device")]
+    UserCancelled,
+
+    #[error("The PIN entered was invalid")]
+    PinInvalid,
+
+    #[error("No matching Trezor device was found")]
+    DeviceNotFound,
+
+    #[error("The device firmware reported an error: {message}")]
+    FirmwareError { message: String },
+
+    #[error("The bridge does not allow this method: {message}")]
+    MethodNotAllowed { message: String },
+}
+
+impl From<std::io::Error> for HardwareError {
+    fn from(err: std::io::Error) -> Self {
+        HardwareError::IoError(err)
+    }
+}
+
+impl From<serde_json::Error> for HardwareError {
+    fn from(err: serde_json::Error) -> Self {
+        HardwareError::JsonError(err)
+    }
+}
+
+impl From<hidapi::HidError> for HardwareError {
+    fn from(err: hidapi::HidError) -> Self {
+        HardwareError::CommunicationError { error_details: err.to_string() }
+    }
+}
+
+/// The structured `{code, message}` error trezor-connect's bridge forwards
+/// for a failed command, ahead of being mapped into a [`HardwareError`]
+/// variant callers can match on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BridgeErrorDetails {
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl From<BridgeErrorDetails> for HardwareError {
+    fn from(err: BridgeErrorDetails) -> Self {
+        match err.code.as_deref() {
+            Some("Method_Cancel") | Some("Failure_ActionCancelled") => HardwareError::UserCancelled,
+            Some("Failure_PinInvalid") | Some("Failure_PinMismatch") => HardwareError::PinInvalid,
+            Some("Device_NotFound") => HardwareError::DeviceNotFound,
+            Some("Failure_FirmwareError") | Some("Failure_ProcessError") => {
+                HardwareError::FirmwareError { message: err.message }
+            }
+            Some("Method_NotAllowed") => HardwareError::MethodNotAllowed { message: err.message },
+            _ => HardwareError::CommunicationError { error_details: err.message },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(code: Option<&str>, message: &str) -> BridgeErrorDetails {
+        BridgeErrorDetails { code: code.map(String::from), message: message.into() }
+    }
+
+    #[test]
+    fn maps_cancellation_codes_to_user_cancelled() {
+        assert!(matches!(HardwareError::from(details(Some("Method_Cancel"), "x")), HardwareError::UserCancelled));
+        assert!(matches!(
+            HardwareError::from(details(Some("Failure_ActionCancelled"), "x")),
+            HardwareError::UserCancelled
+        ));
+    }
+
+    #[test]
+    fn maps_pin_codes_to_pin_invalid() {
+        assert!(matches!(HardwareError::from(details(Some("Failure_PinInvalid"), "x")), HardwareError::PinInvalid));
+        assert!(matches!(HardwareError::from(details(Some("Failure_PinMismatch"), "x")), HardwareError::PinInvalid));
+    }
+
+    #[test]
+    fn maps_device_not_found_code() {
+        assert!(matches!(HardwareError::from(details(Some("Device_NotFound"), "x")), HardwareError::DeviceNotFound));
+    }
+
+    #[test]
+    fn maps_firmware_error_codes_and_keeps_the_message() {
+        let err = HardwareError::from(details(Some("Failure_FirmwareError"), "boom"));
+        assert!(matches!(err, HardwareError::FirmwareError { message } if message == "boom"));
+
+        let err = HardwareError::from(details(Some("Failure_ProcessError"), "boom"));
+        assert!(matches!(err, HardwareError::FirmwareError { message } if message == "boom"));
+    }
+
+    #[test]
+    fn maps_method_not_allowed_code() {
+        let err = HardwareError::from(details(Some("Method_NotAllowed"), "nope"));
+        assert!(matches!(err, HardwareError::MethodNotAllowed { message } if message == "nope"));
+    }
+
+    #[test]
+    fn falls_back_to_communication_error_for_unknown_or_missing_codes() {
+        let err = HardwareError::from(details(Some("Something_Else"), "boom"));
+        assert!(matches!(err, HardwareError::CommunicationError { error_details } if error_details == "boom"));
+
+        let err = HardwareError::from(details(None, "boom"));
+        assert!(matches!(err, HardwareError::CommunicationError { error_details } if error_details == "boom"));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::errors::HardwareError;
+
+/// Callback surface for the multi-step dialogues a Trezor device can demand
+/// mid-command (PIN entry, passphrase entry, physical button confirmation).
+///
+/// `send_command` invokes these as intermediate `ButtonRequest` /
+/// `PinMatrixRequest` / `PassphraseRequest`-style messages arrive from the
+/// bridge, then forwards the reply back to the device as a follow-up
+/// `*Ack` command.
+pub trait Interactor {
+    /// The device wants a PIN entered on the (scrambled) matrix it is
+    /// displaying. Return the digits the user typed.
+    fn request_pin(&self) -> Result<String, HardwareError>;
+
+    /// The device wants a BIP-39 passphrase. Return it as typed by the user.
+    fn request_passphrase(&self) -> Result<String, HardwareError>;
+
+    /// The device is waiting for the user to press its physical button.
+    /// Return once the user has confirmed (or raise an error to abort).
+    fn button_request(&self) -> Result<(), HardwareError>;
+}
+
+/// An [`Interactor`] for commands that are known never to prompt, e.g. because
+/// the device has already been unlocked for the session. Every hook fails
+/// loudly rather than silently acknowledging a request it wasn't built for.
+pub struct NullInteractor;
+
+impl Interactor for NullInteractor {
+    fn request_pin(&self) -> Result<String, HardwareError> {
+        Err(HardwareError::CommunicationError {
+            error_details: "device requested a PIN but no Interactor was supplied".into(),
+        })
+    }
+
+    fn request_passphrase(&self) -> Result<String, HardwareError> {
+        Err(HardwareError::CommunicationError {
+            error_details: "device requested a passphrase but no Interactor was supplied".into(),
+        })
+    }
+
+    fn button_request(&self) -> Result<(), HardwareError> {
+        Err(HardwareError::CommunicationError {
+            error_details: "device requested a button confirmation but no Interactor was supplied".into(),
+        })
+    }
+}
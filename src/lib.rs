@@ -1,86 +1,126 @@
-use std::{io::{BufRead, BufReader, Write}, process::{Command, Stdio}, str};
+use std::collections::HashMap;
 use serde_json::{json, Value};
+use crate::bitcoin::SignTxProgress;
 use crate::errors::HardwareError;
-use crate::types::{AddressInfo, PublicKeyInfo, TrezorClient, TrezorDeviceFeatures, TrezorResponse};
+use crate::types::{AddressInfo, PublicKeyInfo, SignedTx, TrezorDeviceFeatures, TrezorResponse, TxRequest};
 
+mod bitcoin;
 mod errors;
+mod interactor;
+mod monitor;
+mod transport;
 mod types;
+
+pub use crate::interactor::{Interactor, NullInteractor};
+pub use crate::monitor::{DeviceEvent, DeviceMonitor};
+pub use crate::transport::{DenoTransport, NativeTransport, Transport};
+pub use crate::types::{
+    DeviceDescriptor, Eip712TypedData, EthAddressInfo, EthereumSignature, EthereumTransaction,
+    PrevTxMeta, SignTx, TrezorClient, TxInput, TxOutput,
+};
+
 impl TrezorClient {
-    fn new() -> Result<Self, HardwareError> {
-        // Start the Deno script as a persistent process
-        let mut process = Command::new("deno")
-            .arg("run")
-            .arg("--allow-net")
-            .arg("--allow-read")
-            .arg("--allow-env")
-            .arg("--allow-ffi")
-            .arg("--allow-run")
-            .arg("--allow-sys")
-            .arg("--allow-write")
-            .arg("--allow-scripts=npm:blake-hash@2.0.0,npm:tiny-secp256k1@1.1.7,npm:protobufjs@7.4.0,npm:usb@2.15.0")
-            .arg("--node-modules-dir")
-            .arg("functions-with-trezor.js")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .map_err(|e| HardwareError::IoError { error_details: e.to_string() })?;
-
-        let reader = BufReader::new(process.stdout.take().unwrap());
-
-        // Give the script time to start up and print initial instructions
-        std::thread::sleep(std::time::Duration::from_millis(500));
-
-        Ok(TrezorClient { process, reader })
+    /// Spawn the Deno bridge using [`NullInteractor`], i.e. for sessions that
+    /// are known never to need a PIN, passphrase, or button confirmation.
+    pub fn new() -> Result<Self, HardwareError> {
+        Self::new_with_interactor(Box::new(NullInteractor))
     }
 
-    fn send_command(&mut self, command_obj: Value) -> Result<Value, HardwareError> {
-        let command_str = command_obj.to_string();
-
-        // Send command to the script via stdin
-        if let Some(ref mut stdin) = self.process.stdin {
-            writeln!(stdin, "{}", command_str)
-                .map_err(|e| HardwareError::IoError { error_details: e.to_string() })?;
-            stdin.flush()
-                .map_err(|e| HardwareError::IoError { error_details: e.to_string() })?;
-        } else {
-            return Err(HardwareError::CommunicationError { error_details: "Failed to get stdin".into() });
-        }
+    /// Spawn the Deno bridge, routing any mid-command `ButtonRequest` /
+    /// `PinMatrixRequest` / `PassphraseRequest` prompt through `interactor`.
+    pub fn new_with_interactor(interactor: Box<dyn Interactor>) -> Result<Self, HardwareError> {
+        let transport = DenoTransport::spawn()?;
+        Ok(Self::with_transport(Box::new(transport), interactor))
+    }
+
+    /// Build a client around an already-constructed [`Transport`], e.g. a
+    /// [`NativeTransport`] talking directly to USB/HID or the emulator,
+    /// instead of the Deno bridge. Every other method works unchanged
+    /// regardless of which transport backs the client.
+    pub fn with_transport(transport: Box<dyn Transport>, interactor: Box<dyn Interactor>) -> Self {
+        TrezorClient { transport, interactor }
+    }
 
-        // Read response (JSON) from stdout
-        let mut line = String::new();
-        self.reader.read_line(&mut line)
-            .map_err(|e| HardwareError::IoError { error_details: e.to_string() })?;
+    /// List every Trezor device currently visible to the bridge, for
+    /// choosing one to [`Self::connect`] to rather than assuming there's
+    /// exactly one plugged in.
+    pub fn list_devices() -> Result<Vec<DeviceDescriptor>, HardwareError> {
+        let mut bridge = Self::new_with_interactor(Box::new(NullInteractor))?;
+        let response = bridge.send_command(json!({ "command": "enumerate" }))?;
+        let typed_response: TrezorResponse<Vec<DeviceDescriptor>> = serde_json::from_value(response)?;
+        Self::unwrap_payload(typed_response)
+    }
 
-        let s = line.trim();
+    /// Attach to the specific device identified by `descriptor`, as returned
+    /// by [`Self::list_devices`], using [`NullInteractor`].
+    pub fn connect(descriptor: &DeviceDescriptor) -> Result<Self, HardwareError> {
+        Self::connect_with_interactor(descriptor, Box::new(NullInteractor))
+    }
 
-        if s.is_empty() {
-            eprintln!("Warning: Empty output from Deno script");
-            return Ok(json!({ "success": false, "error": "Empty output" }));
+    /// Attach to the specific device identified by `descriptor`, routing any
+    /// mid-command prompt through `interactor`.
+    pub fn connect_with_interactor(
+        descriptor: &DeviceDescriptor,
+        interactor: Box<dyn Interactor>,
+    ) -> Result<Self, HardwareError> {
+        let mut client = Self::new_with_interactor(interactor)?;
+        let response = client.send_command(json!({
+            "command": "select",
+            "path": descriptor.path,
+            "deviceId": descriptor.device_id,
+        }))?;
+        let typed_response: TrezorResponse<()> = serde_json::from_value(response)?;
+        if !typed_response.success {
+            return Err(HardwareError::CommunicationError {
+                error_details: typed_response.error.unwrap_or_else(|| "unknown error".into()),
+            });
         }
+        Ok(client)
+    }
 
-        let v: Value = match serde_json::from_str(s) {
-            Ok(value) => value,
-            Err(e) => {
-                eprintln!("Error parsing JSON: {} (output was: '{}')", e, s);
-                return Ok(json!({ "success": false, "error": format!("JSON parse error: {}", e) }));
+    /// Send `command_obj`, then loop on the transport's replies until a
+    /// terminal `{"success": ...}` response arrives. Intermediate `pin`,
+    /// `passphrase`, or `button` messages are routed through `self.interactor`
+    /// and acknowledged with a `pinAck` / `passphraseAck` / `buttonAck`
+    /// follow-up command. Any other reply (e.g. a `signTx`/`txAck` `TxRequest`)
+    /// carries neither a `success` nor a recognised `type` field, so it is
+    /// handed back to the caller to interpret rather than treated as an error.
+    fn send_command(&mut self, command_obj: Value) -> Result<Value, HardwareError> {
+        let mut response = self.transport.send_streaming(command_obj)?;
+
+        loop {
+            if response.get("success").is_some() {
+                return Ok(response);
             }
-        };
 
-        Ok(v)
+            let follow_up = match response.get("type").and_then(Value::as_str) {
+                Some("pin") => {
+                    let pin = self.interactor.request_pin()?;
+                    json!({ "command": "pinAck", "value": pin })
+                }
+                Some("passphrase") => {
+                    let passphrase = self.interactor.request_passphrase()?;
+                    json!({ "command": "passphraseAck", "value": passphrase })
+                }
+                Some("button") => {
+                    self.interactor.button_request()?;
+                    json!({ "command": "buttonAck" })
+                }
+                _ => return Ok(response),
+            };
+            response = self.transport.send_streaming(follow_up)?;
+        }
     }
 
     fn init(&mut self) -> Result<TrezorResponse<()>, HardwareError> {
         let response = self.send_command(json!({ "command": "init" }))?;
-        let typed_response: TrezorResponse<()> = serde_json::from_value(response)
-            .map_err(|e| HardwareError::JsonError { error_details: e.to_string() })?;
+        let typed_response: TrezorResponse<()> = serde_json::from_value(response)?;
         Ok(typed_response)
     }
 
     fn get_features(&mut self) -> Result<TrezorResponse<TrezorDeviceFeatures>, HardwareError> {
         let response = self.send_command(json!({ "command": "getFeatures" }))?;
-        let typed_response: TrezorResponse<TrezorDeviceFeatures> = serde_json::from_value(response)
-            .map_err(|e| HardwareError::JsonError { error_details: e.to_string() })?;
+        let typed_response: TrezorResponse<TrezorDeviceFeatures> = serde_json::from_value(response)?;
         Ok(typed_response)
     }
 
@@ -90,8 +130,7 @@ impl TrezorClient {
             "path": path,
             "coin": coin
         }))?;
-        let typed_response: TrezorResponse<PublicKeyInfo> = serde_json::from_value(response)
-            .map_err(|e| HardwareError::JsonError { error_details: e.to_string() })?;
+        let typed_response: TrezorResponse<PublicKeyInfo> = serde_json::from_value(response)?;
         Ok(typed_response)
     }
 
@@ -102,24 +141,118 @@ impl TrezorClient {
             "coin": coin,
             "showOnTrezor": show_on_trezor
         }))?;
-        let typed_response: TrezorResponse<AddressInfo> = serde_json::from_value(response)
-            .map_err(|e| HardwareError::JsonError { error_details: e.to_string() })?;
+        let typed_response: TrezorResponse<AddressInfo> = serde_json::from_value(response)?;
         Ok(typed_response)
     }
 
     fn exit(&mut self) -> Result<TrezorResponse<()>, HardwareError> {
         let response = self.send_command(json!({ "command": "exit" }))?;
-        let typed_response: TrezorResponse<()> = serde_json::from_value(response)
-            .map_err(|e| HardwareError::JsonError { error_details: e.to_string() })?;
+        let typed_response: TrezorResponse<()> = serde_json::from_value(response)?;
         Ok(typed_response)
     }
-}
 
-impl Drop for TrezorClient {
-    fn drop(&mut self) {
-        // Try to properly close the connection when done
-        let _ = self.exit();
-        let _ = self.process.wait();
+    /// Sign a Bitcoin transaction. `prev_txs` must contain the full previous
+    /// transaction, keyed by its hex tx hash, for every input that spends an
+    /// output the device doesn't already know about.
+    ///
+    /// trezor-connect's own `signTransaction` call completes the device's
+    /// `TxRequest`/`TxAck` dialogue internally and hands back one terminal
+    /// `{success, payload}` response, same as every other bridge command —
+    /// so the initial `signTx` command here carries the full transaction
+    /// up front, for a bridge that answers that way. A transport that talks
+    /// to the device directly instead (no bridge process driving the
+    /// dialogue for us) has no such shortcut: its first reply is the
+    /// device's own first `TxRequest`, with neither a `success` nor a
+    /// `type` field, which [`Self::send_command`] hands back unchanged. In
+    /// that case [`SignTxProgress`] drives the remaining `TxRequest`/`TxAck`
+    /// round trips to completion.
+    pub fn sign_tx(
+        &mut self,
+        sign_tx: SignTx,
+        prev_txs: HashMap<String, PrevTxMeta>,
+    ) -> Result<String, HardwareError> {
+        let response = self.send_command(json!({
+            "command": "signTx",
+            "coin": sign_tx.coin,
+            "inputs": sign_tx.inputs,
+            "outputs": sign_tx.outputs,
+            "refTxs": prev_txs,
+            "inputsCount": sign_tx.inputs.len(),
+            "outputsCount": sign_tx.outputs.len(),
+        }))?;
+
+        if response.get("success").is_some() {
+            let typed_response: TrezorResponse<SignedTx> = serde_json::from_value(response)?;
+            return Ok(Self::unwrap_payload(typed_response)?.serialized_tx);
+        }
+
+        let first_request: TxRequest = serde_json::from_value(response)?;
+        let mut progress = SignTxProgress::new(sign_tx, prev_txs, first_request);
+        while !progress.is_finished() {
+            let ack = progress.build_ack()?;
+            let response = self.send_command(json!({ "command": "txAck", "ack": ack }))?;
+            let next: TxRequest = serde_json::from_value(response)?;
+            progress.advance(next)?;
+        }
+        progress.finish()
+    }
+
+    /// Derive an Ethereum address at `path`.
+    pub fn get_eth_address(&mut self, path: &str) -> Result<EthAddressInfo, HardwareError> {
+        let response = self.send_command(json!({ "command": "ethGetAddress", "path": path }))?;
+        let typed_response: TrezorResponse<EthAddressInfo> = serde_json::from_value(response)?;
+        Self::unwrap_payload(typed_response)
+    }
+
+    /// Sign an Ethereum transaction at `path`, returning the `(v, r, s)`
+    /// signature components so the caller can assemble the signed RLP.
+    pub fn sign_eth_tx(&mut self, path: &str, tx: EthereumTransaction) -> Result<(String, String, String), HardwareError> {
+        let response = self.send_command(json!({
+            "command": "ethSignTransaction",
+            "path": path,
+            "tx": tx,
+        }))?;
+        let typed_response: TrezorResponse<EthereumSignature> = serde_json::from_value(response)?;
+        let signature = Self::unwrap_payload(typed_response)?;
+        Ok((signature.v, signature.r, signature.s))
+    }
+
+    /// Sign an arbitrary message at `path` using the Ethereum personal-sign
+    /// scheme.
+    pub fn sign_eth_message(&mut self, path: &str, message: &str) -> Result<EthereumSignature, HardwareError> {
+        let response = self.send_command(json!({
+            "command": "ethSignMessage",
+            "path": path,
+            "message": message,
+        }))?;
+        let typed_response: TrezorResponse<EthereumSignature> = serde_json::from_value(response)?;
+        Self::unwrap_payload(typed_response)
+    }
+
+    /// Sign an EIP-712 typed-data payload at `path`.
+    pub fn sign_eth_typed_data(&mut self, path: &str, typed_data: Eip712TypedData) -> Result<EthereumSignature, HardwareError> {
+        let response = self.send_command(json!({
+            "command": "ethSignTypedData",
+            "path": path,
+            "data": typed_data,
+        }))?;
+        let typed_response: TrezorResponse<EthereumSignature> = serde_json::from_value(response)?;
+        Self::unwrap_payload(typed_response)
+    }
+
+    /// Unwrap a `TrezorResponse`, turning a `success: false` reply into an
+    /// `Err` carrying its error message instead of leaving callers to check
+    /// `.success` by hand.
+    fn unwrap_payload<T>(response: TrezorResponse<T>) -> Result<T, HardwareError> {
+        if response.success {
+            response.payload.ok_or_else(|| HardwareError::CommunicationError {
+                error_details: "device reported success but returned no payload".into(),
+            })
+        } else {
+            Err(HardwareError::CommunicationError {
+                error_details: response.error.unwrap_or_else(|| "unknown error".into()),
+            })
+        }
     }
 }
 
@@ -213,4 +346,83 @@ mod tests {
             }
         }
     }
+
+    /// A [`Transport`] that plays back a fixed script of replies, one per
+    /// `send`, for driving `sign_tx` without real hardware.
+    struct ScriptedTransport {
+        replies: std::collections::VecDeque<Value>,
+    }
+
+    impl Transport for ScriptedTransport {
+        fn send(&mut self, _command: Value) -> Result<Value, HardwareError> {
+            self.replies.pop_front().ok_or_else(|| HardwareError::CommunicationError {
+                error_details: "scripted transport ran out of replies".into(),
+            })
+        }
+    }
+
+    #[test]
+    fn sign_tx_drives_raw_tx_request_dialogue_to_completion() {
+        // Unlike every other bridge command, a transport that talks to the
+        // device directly (no bridge process completing the dialogue for
+        // us) answers `signTx` with a stream of bare `TxRequest` objects
+        // rather than one terminal `{success, payload}` reply.
+        let sign_tx = SignTx {
+            coin: "Bitcoin".into(),
+            inputs: vec![TxInput {
+                address_n: vec![0x8000_002c, 0x8000_0000, 0x8000_0000],
+                prev_hash: "deadbeef".into(),
+                prev_index: 0,
+                amount: 100_000,
+                script_type: None,
+                sequence: None,
+            }],
+            outputs: vec![TxOutput {
+                address: Some("1BoatSLRHtKNngkdXEeobR76b53LETtpyT".into()),
+                address_n: None,
+                amount: 99_000,
+                script_type: "PAYTOADDRESS".into(),
+            }],
+        };
+
+        let replies = vec![
+            json!({
+                "requestType": "TXINPUT",
+                "details": { "requestIndex": 0, "txHash": null },
+                "serialized": null,
+            }),
+            json!({
+                "requestType": "TXOUTPUT",
+                "details": { "requestIndex": 0, "txHash": null },
+                "serialized": { "signatureIndex": null, "signature": null, "serializedTx": "aa" },
+            }),
+            json!({
+                "requestType": "TXFINISHED",
+                "details": { "requestIndex": 0, "txHash": null },
+                "serialized": { "signatureIndex": null, "signature": null, "serializedTx": "bb" },
+            }),
+        ];
+        let transport = ScriptedTransport { replies: replies.into() };
+        let mut client = TrezorClient::with_transport(Box::new(transport), Box::new(NullInteractor));
+
+        let serialized_tx = client.sign_tx(sign_tx, HashMap::new()).expect("sign_tx should complete");
+        assert_eq!(serialized_tx, "aabb");
+    }
+
+    #[test]
+    fn sign_tx_returns_terminal_payload_directly_when_bridge_completes_it() {
+        // trezor-connect's `signTransaction` drives the TxRequest/TxAck
+        // dialogue with the device itself and hands back one finished
+        // response, like every other bridge command.
+        let sign_tx = SignTx { coin: "Bitcoin".into(), inputs: vec![], outputs: vec![] };
+        let replies = vec![json!({
+            "success": true,
+            "payload": { "serializedTx": "cafe" },
+        })];
+        let transport = ScriptedTransport { replies: replies.into() };
+        let mut client = TrezorClient::with_transport(Box::new(transport), Box::new(NullInteractor));
+
+        let serialized_tx = client.sign_tx(sign_tx, HashMap::new()).expect("sign_tx should complete");
+        assert_eq!(serialized_tx, "cafe");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::types::DeviceDescriptor;
+use crate::TrezorClient;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A device arriving or leaving, as reported by [`DeviceMonitor`].
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    Arrived(DeviceDescriptor),
+    Left(String),
+}
+
+/// Polls for Trezor devices being plugged in or unplugged on a single
+/// background thread, delivering [`DeviceEvent`]s over a channel instead of
+/// leaving applications to blindly construct a [`TrezorClient`] and fail.
+///
+/// Each poll re-enumerates via [`TrezorClient::list_devices`], which spawns
+/// a short-lived bridge process; this keeps the monitor down to one thread
+/// regardless of how many devices are tracked, at the cost of each tick's
+/// latency being bounded by the bridge's own startup time.
+pub struct DeviceMonitor {
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DeviceMonitor {
+    /// Start polling immediately, returning the monitor and the receiving
+    /// end of its event channel. Dropping the monitor stops the thread and
+    /// joins it.
+    pub fn start() -> (Self, Receiver<DeviceEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = Arc::clone(&shutdown);
+
+        let handle = thread::spawn(move || {
+            let mut known: HashSet<String> = HashSet::new();
+
+            while !thread_shutdown.load(Ordering::Relaxed) {
+                // Treat a failed poll as "no change" rather than "every
+                // device left": a transient bridge hiccup would otherwise
+                // fire a spurious Left for every known device, followed by
+                // an equally spurious Arrived once the next poll succeeds.
+                let devices = match TrezorClient::list_devices() {
+                    Ok(devices) => devices,
+                    Err(_) => {
+                        thread::sleep(POLL_INTERVAL);
+                        continue;
+                    }
+                };
+
+                for device in &devices {
+                    let key = device_key(device);
+                    if !known.contains(&key) && tx.send(DeviceEvent::Arrived(device.clone())).is_err() {
+                        return;
+                    }
+                }
+                let seen = devices.iter().map(device_key).collect::<HashSet<_>>();
+
+                for key in known.difference(&seen) {
+                    if tx.send(DeviceEvent::Left(key.clone())).is_err() {
+                        return;
+                    }
+                }
+                known = seen;
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+
+        (Self { shutdown, handle: Some(handle) }, rx)
+    }
+}
+
+fn device_key(device: &DeviceDescriptor) -> String {
+    device.device_id.clone().unwrap_or_else(|| device.path.clone())
+}
+
+impl Drop for DeviceMonitor {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
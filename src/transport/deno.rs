@@ -0,0 +1,93 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use serde_json::{json, Value};
+use crate::errors::{BridgeErrorDetails, HardwareError};
+use crate::transport::Transport;
+
+/// The original transport: a persistent `deno run functions-with-trezor.js`
+/// subprocess, spoken to over newline-delimited JSON on stdin/stdout.
+pub struct DenoTransport {
+    process: Child,
+    reader: BufReader<ChildStdout>,
+}
+
+impl DenoTransport {
+    /// Spawn the Deno bridge script as a persistent child process.
+    pub fn spawn() -> Result<Self, HardwareError> {
+        let mut process = Command::new("deno")
+            .arg("run")
+            .arg("--allow-net")
+            .arg("--allow-read")
+            .arg("--allow-env")
+            .arg("--allow-ffi")
+            .arg("--allow-run")
+            .arg("--allow-sys")
+            .arg("--allow-write")
+            .arg("--allow-scripts=npm:blake-hash@2.0.0,npm:tiny-secp256k1@1.1.7,npm:protobufjs@7.4.0,npm:usb@2.15.0")
+            .arg("--node-modules-dir")
+            .arg("functions-with-trezor.js")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        let reader = BufReader::new(process.stdout.take().unwrap());
+
+        // Give the script time to start up and print initial instructions
+        std::thread::sleep(std::time::Duration::from_millis(500));
+
+        Ok(Self { process, reader })
+    }
+
+    fn write_command(&mut self, command_obj: &Value) -> Result<(), HardwareError> {
+        if let Some(ref mut stdin) = self.process.stdin {
+            writeln!(stdin, "{}", command_obj)?;
+            stdin.flush()?;
+            Ok(())
+        } else {
+            Err(HardwareError::CommunicationError { error_details: "Failed to get stdin".into() })
+        }
+    }
+
+    fn read_response(&mut self) -> Result<Value, HardwareError> {
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+
+        let s = line.trim();
+
+        if s.is_empty() {
+            return Err(HardwareError::CommunicationError {
+                error_details: "bridge produced no output".into(),
+            });
+        }
+
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+impl Transport for DenoTransport {
+    fn send(&mut self, command: Value) -> Result<Value, HardwareError> {
+        self.write_command(&command)?;
+        let response = self.read_response()?;
+
+        if response.get("success") == Some(&Value::Bool(false)) {
+            if let Some(error) = response.get("error") {
+                if let Ok(details) = serde_json::from_value::<BridgeErrorDetails>(error.clone()) {
+                    return Err(HardwareError::from(details));
+                }
+            }
+            return Err(HardwareError::CommunicationError {
+                error_details: "bridge reported failure without a structured error".into(),
+            });
+        }
+
+        Ok(response)
+    }
+}
+
+impl Drop for DenoTransport {
+    fn drop(&mut self) {
+        let _ = self.write_command(&json!({ "command": "exit" }));
+        let _ = self.process.wait();
+    }
+}
@@ -0,0 +1,28 @@
+use serde_json::Value;
+use crate::errors::HardwareError;
+
+mod deno;
+mod native;
+mod wire;
+
+pub use deno::DenoTransport;
+pub use native::NativeTransport;
+
+/// Abstraction over how a command reaches the physical (or emulated)
+/// device. [`DenoTransport`] shells out to the existing Deno/trezor-connect
+/// bridge; [`NativeTransport`] speaks the Trezor wire protocol directly over
+/// USB/HID or the emulator's UDP port. `TrezorClient` only ever talks to
+/// this trait — the wire format is entirely the transport's concern.
+pub trait Transport {
+    /// Send one command and return the device/bridge's immediate reply.
+    fn send(&mut self, command: Value) -> Result<Value, HardwareError>;
+
+    /// Send a command that is itself one step of a longer dialogue
+    /// (PIN/passphrase/button prompts, or a `sign_tx` `TxRequest` stream)
+    /// rather than a standalone round trip. Transports that don't need to
+    /// distinguish the two can rely on the default, which just forwards to
+    /// [`Self::send`].
+    fn send_streaming(&mut self, command: Value) -> Result<Value, HardwareError> {
+        self.send(command)
+    }
+}
@@ -0,0 +1,357 @@
+//! A minimal hand-rolled protobuf codec for the subset of the Trezor wire
+//! protocol's `messages.proto` that [`super::NativeTransport`] speaks:
+//! `Initialize`/`Features`, `GetPublicKey`/`PublicKey`, `GetAddress`/
+//! `Address`, the `Button`/`PinMatrix`/`Passphrase` request/ack pairs, and
+//! `Success`/`Failure`. There is no `prost`/`protobuf` dependency available
+//! in this tree, so field encoding/decoding is done by hand with plain
+//! varints and length-delimited records — no nested nor repeated-packed
+//! fields are needed for this subset, so the minimal wire-format primitives
+//! below are all that's required.
+//!
+//! `signTx`/`txAck` and the Ethereum commands are not mapped here yet; see
+//! [`encode_command`]'s fallthrough.
+
+use std::collections::HashMap;
+use serde_json::{json, Value};
+use crate::errors::HardwareError;
+
+/// `MessageType` values from the Trezor wire protocol, scoped to the
+/// commands this codec implements.
+mod message_type {
+    pub const INITIALIZE: u16 = 0;
+    pub const SUCCESS: u16 = 2;
+    pub const FAILURE: u16 = 3;
+    pub const GET_PUBLIC_KEY: u16 = 11;
+    pub const PUBLIC_KEY: u16 = 12;
+    pub const FEATURES: u16 = 17;
+    pub const PIN_MATRIX_REQUEST: u16 = 18;
+    pub const PIN_MATRIX_ACK: u16 = 19;
+    pub const BUTTON_REQUEST: u16 = 26;
+    pub const BUTTON_ACK: u16 = 27;
+    pub const GET_ADDRESS: u16 = 29;
+    pub const ADDRESS: u16 = 30;
+    pub const PASSPHRASE_REQUEST: u16 = 41;
+    pub const PASSPHRASE_ACK: u16 = 42;
+    pub const GET_FEATURES: u16 = 55;
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            break;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_tag(buf: &mut Vec<u8>, field_number: u32, wire_type: u8) {
+    write_varint(buf, ((field_number as u64) << 3) | wire_type as u64);
+}
+
+fn write_uint32_field(buf: &mut Vec<u8>, field_number: u32, value: u32) {
+    write_tag(buf, field_number, 0);
+    write_varint(buf, value as u64);
+}
+
+fn write_bool_field(buf: &mut Vec<u8>, field_number: u32, value: bool) {
+    write_uint32_field(buf, field_number, value as u32);
+}
+
+fn write_string_field(buf: &mut Vec<u8>, field_number: u32, value: &str) {
+    write_tag(buf, field_number, 2);
+    write_varint(buf, value.len() as u64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+fn write_repeated_uint32_field(buf: &mut Vec<u8>, field_number: u32, values: &[u32]) {
+    for value in values {
+        write_uint32_field(buf, field_number, *value);
+    }
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64, HardwareError> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos).ok_or_else(|| HardwareError::CommunicationError {
+            error_details: "truncated varint in protobuf message".into(),
+        })?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+enum FieldValue {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+/// Parse `data` into a map of field number to every value that field
+/// carried, in wire order. Good enough for the flat (no nested message)
+/// fields this codec's commands use.
+fn parse_fields(data: &[u8]) -> Result<HashMap<u32, Vec<FieldValue>>, HardwareError> {
+    let mut fields: HashMap<u32, Vec<FieldValue>> = HashMap::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let tag = read_varint(data, &mut pos)?;
+        let field_number = (tag >> 3) as u32;
+        let wire_type = (tag & 0x7) as u8;
+        let value = match wire_type {
+            0 => FieldValue::Varint(read_varint(data, &mut pos)?),
+            2 => {
+                let len = read_varint(data, &mut pos)? as usize;
+                let end = pos + len;
+                let bytes = data.get(pos..end).ok_or_else(|| HardwareError::CommunicationError {
+                    error_details: "truncated length-delimited field in protobuf message".into(),
+                })?;
+                pos = end;
+                FieldValue::Bytes(bytes.to_vec())
+            }
+            other => {
+                return Err(HardwareError::CommunicationError {
+                    error_details: format!("unsupported protobuf wire type {other}"),
+                });
+            }
+        };
+        fields.entry(field_number).or_default().push(value);
+    }
+    Ok(fields)
+}
+
+fn field_string(fields: &HashMap<u32, Vec<FieldValue>>, field_number: u32) -> Option<String> {
+    match fields.get(&field_number)?.first()? {
+        FieldValue::Bytes(bytes) => String::from_utf8(bytes.clone()).ok(),
+        FieldValue::Varint(_) => None,
+    }
+}
+
+fn field_u32(fields: &HashMap<u32, Vec<FieldValue>>, field_number: u32) -> Option<u32> {
+    match fields.get(&field_number)?.first()? {
+        FieldValue::Varint(value) => Some(*value as u32),
+        FieldValue::Bytes(_) => None,
+    }
+}
+
+fn field_bool(fields: &HashMap<u32, Vec<FieldValue>>, field_number: u32) -> bool {
+    field_u32(fields, field_number).unwrap_or(0) != 0
+}
+
+/// Map a `Failure` message's numeric `FailureType` (field 1 of
+/// `messages.proto`) onto the same string codes
+/// [`crate::errors::BridgeErrorDetails`] expects from the bridge, so a
+/// device failure maps to the same [`crate::errors::HardwareError`]
+/// variant regardless of which transport reported it.
+fn failure_code_name(code: u32) -> Option<&'static str> {
+    match code {
+        4 => Some("Failure_ActionCancelled"),
+        7 => Some("Failure_PinInvalid"),
+        12 => Some("Failure_PinMismatch"),
+        9 => Some("Failure_ProcessError"),
+        99 => Some("Failure_FirmwareError"),
+        _ => None,
+    }
+}
+
+/// Parse a `path` like `m/44'/0'/0'` into BIP-32 components, hardening each
+/// `'`-suffixed index by setting its top bit, as `GetAddress`/`GetPublicKey`
+/// expect for `address_n`.
+pub(super) fn parse_bip32_path(path: &str) -> Result<Vec<u32>, HardwareError> {
+    path.trim_start_matches("m/")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let (index, hardened) = match segment.strip_suffix(['\'', 'h']) {
+                Some(stripped) => (stripped, true),
+                None => (segment, false),
+            };
+            let index: u32 = index.parse().map_err(|_| HardwareError::CommunicationError {
+                error_details: format!("invalid BIP-32 path segment: {segment}"),
+            })?;
+            Ok(if hardened { index | 0x8000_0000 } else { index })
+        })
+        .collect()
+}
+
+/// Encode `command` (the same JSON shape every [`crate::transport::Transport`]
+/// impl accepts) into a wire message type and its protobuf body.
+pub(super) fn encode_command(command: &Value) -> Result<(u16, Vec<u8>), HardwareError> {
+    let name = command.get("command").and_then(Value::as_str).unwrap_or_default();
+    let mut buf = Vec::new();
+
+    match name {
+        "init" => Ok((message_type::INITIALIZE, buf)),
+        "getFeatures" => Ok((message_type::GET_FEATURES, buf)),
+        "buttonAck" => Ok((message_type::BUTTON_ACK, buf)),
+        "pinAck" => {
+            let pin = command.get("value").and_then(Value::as_str).unwrap_or_default();
+            write_string_field(&mut buf, 1, pin);
+            Ok((message_type::PIN_MATRIX_ACK, buf))
+        }
+        "passphraseAck" => {
+            let passphrase = command.get("value").and_then(Value::as_str).unwrap_or_default();
+            write_string_field(&mut buf, 1, passphrase);
+            Ok((message_type::PASSPHRASE_ACK, buf))
+        }
+        "getpk" => {
+            let path = command.get("path").and_then(Value::as_str).unwrap_or_default();
+            write_repeated_uint32_field(&mut buf, 1, &parse_bip32_path(path)?);
+            if let Some(coin) = command.get("coin").and_then(Value::as_str) {
+                write_string_field(&mut buf, 2, coin);
+            }
+            Ok((message_type::GET_PUBLIC_KEY, buf))
+        }
+        "getaddr" => {
+            let path = command.get("path").and_then(Value::as_str).unwrap_or_default();
+            write_repeated_uint32_field(&mut buf, 1, &parse_bip32_path(path)?);
+            if let Some(coin) = command.get("coin").and_then(Value::as_str) {
+                write_string_field(&mut buf, 2, coin);
+            }
+            let show_display = command.get("showOnTrezor").and_then(Value::as_bool).unwrap_or(false);
+            write_bool_field(&mut buf, 3, show_display);
+            Ok((message_type::GET_ADDRESS, buf))
+        }
+        other => Err(HardwareError::CommunicationError {
+            error_details: format!(
+                "NativeTransport's wire codec does not support the \"{other}\" command yet \
+                 (only init/getFeatures/getpk/getaddr and the pin/passphrase/button ack commands are wired up)"
+            ),
+        }),
+    }
+}
+
+/// Decode a `(message_type, payload)` reply into the same JSON shape
+/// [`crate::transport::DenoTransport`] would have produced, so `TrezorClient`
+/// doesn't need to care which transport it's talking to.
+pub(super) fn decode_reply(message_type: u16, payload: &[u8]) -> Result<Value, HardwareError> {
+    match message_type {
+        message_type::FEATURES => {
+            let fields = parse_fields(payload)?;
+            Ok(json!({
+                "success": true,
+                "payload": {
+                    "vendor": field_string(&fields, 1).unwrap_or_default(),
+                    "majorVersion": field_u32(&fields, 2).unwrap_or_default(),
+                    "minorVersion": field_u32(&fields, 3).unwrap_or_default(),
+                    "patchVersion": field_u32(&fields, 4).unwrap_or_default(),
+                    "deviceId": field_string(&fields, 5).unwrap_or_default(),
+                    "label": field_string(&fields, 6),
+                    "initialized": field_bool(&fields, 7),
+                },
+            }))
+        }
+        message_type::PUBLIC_KEY => {
+            // `path`/`serializedPath` aren't part of the device's reply (the
+            // real `PublicKey` message only carries the derived key
+            // material) — `NativeTransport::send` fills those back in from
+            // the request it already has on hand.
+            let fields = parse_fields(payload)?;
+            Ok(json!({
+                "success": true,
+                "payload": {
+                    "xpub": field_string(&fields, 1).unwrap_or_default(),
+                    "chainCode": field_string(&fields, 2).unwrap_or_default(),
+                    "publicKey": field_string(&fields, 3).unwrap_or_default(),
+                },
+            }))
+        }
+        message_type::ADDRESS => {
+            let fields = parse_fields(payload)?;
+            Ok(json!({
+                "success": true,
+                "payload": {
+                    "address": field_string(&fields, 1).unwrap_or_default(),
+                },
+            }))
+        }
+        message_type::BUTTON_REQUEST => Ok(json!({ "type": "button" })),
+        message_type::PIN_MATRIX_REQUEST => Ok(json!({ "type": "pin" })),
+        message_type::PASSPHRASE_REQUEST => Ok(json!({ "type": "passphrase" })),
+        message_type::SUCCESS => Ok(json!({ "success": true, "payload": Value::Null })),
+        message_type::FAILURE => {
+            let fields = parse_fields(payload)?;
+            let message = field_string(&fields, 2).unwrap_or_else(|| "unknown device failure".into());
+            let code = field_u32(&fields, 1).and_then(failure_code_name).map(String::from);
+            Err(crate::errors::BridgeErrorDetails { code, message }.into())
+        }
+        other => Err(HardwareError::CommunicationError {
+            error_details: format!("NativeTransport's wire codec received an unrecognised message type {other}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hardened_and_unhardened_bip32_path_segments() {
+        assert_eq!(parse_bip32_path("m/44'/0'/0'").unwrap(), vec![0x8000_002c, 0x8000_0000, 0x8000_0000]);
+        assert_eq!(parse_bip32_path("m/44h/0/5").unwrap(), vec![0x8000_002c, 0, 5]);
+    }
+
+    #[test]
+    fn rejects_a_non_numeric_path_segment() {
+        assert!(parse_bip32_path("m/abc'").is_err());
+    }
+
+    #[test]
+    fn encodes_getaddr_and_decodes_its_address_reply() {
+        let command = json!({ "command": "getaddr", "path": "m/44'/0'/0'", "coin": "Bitcoin", "showOnTrezor": true });
+        let (message_type, body) = encode_command(&command).unwrap();
+        assert_eq!(message_type, message_type::GET_ADDRESS);
+        assert!(!body.is_empty());
+
+        // address = "1A1zP1..." as a length-delimited field 1
+        let mut payload = Vec::new();
+        write_string_field(&mut payload, 1, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+        let reply = decode_reply(message_type::ADDRESS, &payload).unwrap();
+        assert_eq!(reply["success"], true);
+        assert_eq!(reply["payload"]["address"], "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    }
+
+    #[test]
+    fn decodes_a_features_reply() {
+        let mut payload = Vec::new();
+        write_string_field(&mut payload, 1, "trezor.io");
+        write_uint32_field(&mut payload, 2, 2);
+        write_uint32_field(&mut payload, 3, 6);
+        write_uint32_field(&mut payload, 4, 3);
+        write_string_field(&mut payload, 5, "ABCD1234");
+        write_bool_field(&mut payload, 7, true);
+
+        let reply = decode_reply(message_type::FEATURES, &payload).unwrap();
+        assert_eq!(reply["payload"]["vendor"], "trezor.io");
+        assert_eq!(reply["payload"]["majorVersion"], 2);
+        assert_eq!(reply["payload"]["deviceId"], "ABCD1234");
+        assert_eq!(reply["payload"]["initialized"], true);
+    }
+
+    #[test]
+    fn decodes_a_failure_reply_as_an_error() {
+        let mut payload = Vec::new();
+        write_string_field(&mut payload, 2, "PIN invalid");
+        let err = decode_reply(message_type::FAILURE, &payload).unwrap_err();
+        assert!(matches!(err, HardwareError::CommunicationError { error_details } if error_details == "PIN invalid"));
+    }
+
+    #[test]
+    fn maps_a_failure_reply_s_code_to_the_same_variant_the_bridge_would_report() {
+        let mut payload = Vec::new();
+        write_uint32_field(&mut payload, 1, 4); // Failure_ActionCancelled
+        write_string_field(&mut payload, 2, "cancelled by user");
+        let err = decode_reply(message_type::FAILURE, &payload).unwrap_err();
+        assert!(matches!(err, HardwareError::UserCancelled));
+    }
+
+    #[test]
+    fn rejects_a_command_the_codec_does_not_support_yet() {
+        assert!(encode_command(&json!({ "command": "ethSignTransaction" })).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::interactor::Interactor;
+use crate::transport::Transport;
+
+pub struct TrezorClient {
+    pub(crate) transport: Box<dyn Transport>,
+    pub(crate) interactor: Box<dyn Interactor>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrezorResponse<T> {
+    pub success: bool,
+    pub payload: Option<T>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyInfo {
+    pub xpub: String,
+    pub chain_code: String,
+    pub public_key: String,
+    pub path: Vec<u32>,
+    pub serialized_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressInfo {
+    pub address: String,
+    pub path: Vec<u32>,
+    pub serialized_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrezorDeviceFeatures {
+    pub vendor: String,
+    pub major_version: u32,
+    pub minor_version: u32,
+    pub patch_version: u32,
+    pub device_id: String,
+    pub label: Option<String>,
+    pub initialized: bool,
+}
+
+/// One input of a transaction being signed or looked up, identified either
+/// by a BIP-32 path (an input we own) or a previous output being spent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxInput {
+    pub address_n: Vec<u32>,
+    pub prev_hash: String,
+    pub prev_index: u32,
+    pub amount: u64,
+    pub script_type: Option<String>,
+    pub sequence: Option<u32>,
+}
+
+/// One output of a transaction being signed or looked up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxOutput {
+    pub address: Option<String>,
+    pub address_n: Option<Vec<u32>>,
+    pub amount: u64,
+    pub script_type: String,
+}
+
+/// The inputs, outputs, and coin of a transaction to be signed with
+/// [`crate::TrezorClient::sign_tx`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignTx {
+    pub coin: String,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+}
+
+/// The metadata and inputs/outputs of a previously broadcast transaction,
+/// supplied so the device can verify an input's amount against its
+/// previous output without trusting the host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrevTxMeta {
+    pub version: u32,
+    pub lock_time: u32,
+    pub inputs: Vec<TxInput>,
+    pub outputs: Vec<TxOutput>,
+}
+
+/// What a `TxRequest` from the device is asking the host to supply next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TxRequestType {
+    #[serde(rename = "TXINPUT")]
+    TxInput,
+    #[serde(rename = "TXOUTPUT")]
+    TxOutput,
+    #[serde(rename = "TXMETA")]
+    TxMeta,
+    #[serde(rename = "TXFINISHED")]
+    TxFinished,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxRequestDetails {
+    pub request_index: u32,
+    pub tx_hash: Option<String>,
+}
+
+/// A chunk of the partially-serialized signed transaction, emitted
+/// incrementally alongside each `TxRequest` until `TXFINISHED`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedChunk {
+    pub signature_index: Option<u32>,
+    pub signature: Option<String>,
+    pub serialized_tx: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxRequest {
+    pub request_type: TxRequestType,
+    pub details: TxRequestDetails,
+    pub serialized: Option<SerializedChunk>,
+}
+
+/// The terminal payload a bridge that completes `signTx` in one round trip
+/// (rather than streaming `TxRequest`s back for the host to drive) replies
+/// with, mirroring how every other bridge command here finishes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTx {
+    pub serialized_tx: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthAddressInfo {
+    pub address: String,
+    pub path: Vec<u32>,
+}
+
+/// An Ethereum transaction to be signed with
+/// [`crate::TrezorClient::sign_eth_tx`]. `nonce`, `gas_limit`, and `value`
+/// are hex strings (e.g. `"0x01"`); omit `gas_price` and set
+/// `max_fee_per_gas`/`max_priority_fee_per_gas` for an EIP-1559 transaction.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthereumTransaction {
+    pub nonce: String,
+    pub gas_price: Option<String>,
+    pub max_fee_per_gas: Option<String>,
+    pub max_priority_fee_per_gas: Option<String>,
+    pub gas_limit: String,
+    pub to: String,
+    pub value: String,
+    pub data: Option<String>,
+    pub chain_id: u64,
+}
+
+/// The recoverable ECDSA signature returned by the Ethereum signing
+/// commands.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EthereumSignature {
+    pub v: String,
+    pub r: String,
+    pub s: String,
+}
+
+/// A device found by [`crate::TrezorClient::list_devices`], identifying it
+/// well enough to reconnect to it specifically via
+/// [`crate::TrezorClient::connect`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDescriptor {
+    pub path: String,
+    pub device_id: Option<String>,
+    pub model: Option<String>,
+    pub label: Option<String>,
+    pub initialized: bool,
+}
+
+/// An EIP-712 typed-data payload for
+/// [`crate::TrezorClient::sign_eth_typed_data`]. `domain`, `types`, and
+/// `message` are passed through as raw JSON, mirroring the shape
+/// `eth_signTypedData` callers already have on hand.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712TypedData {
+    pub domain: Value,
+    pub types: Value,
+    pub message: Value,
+    pub primary_type: String,
+}